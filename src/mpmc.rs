@@ -2,6 +2,17 @@
 //!
 //! NOTE: This module is not available on targets that do *not* support CAS operations, e.g. ARMv6-M
 //!
+//! In addition to the non-blocking [`MpMcQueue::enqueue`]/[`MpMcQueue::dequeue`], blocking
+//! variants ([`MpMcQueue::blocking_enqueue`], [`MpMcQueue::blocking_dequeue`], and their
+//! `_timeout` counterparts on `std`) are available, parameterized over a [`WaitStrategy`] that
+//! controls how the calling thread waits. [`MpMcQueue::enqueue_slice`]/
+//! [`MpMcQueue::dequeue_slice`] move a whole batch with a single compare-and-swap, and
+//! [`MpMcQueue::drain`] iterates the queue empty. [`MpMcQueue::close`] lets producers signal that
+//! no more items are coming, so consumers can drain the rest and stop deterministically instead
+//! of waiting forever. [`MpMcQueue::len`]/[`MpMcQueue::capacity`]/[`MpMcQueue::is_empty`]/
+//! [`MpMcQueue::is_full`] give a best-effort snapshot of occupancy for backpressure decisions and
+//! metrics.
+//!
 //! # Example
 //!
 //! This queue can be constructed in "const context". Placing it in a `static` variable lets *all*
@@ -70,6 +81,11 @@
 //! - The optimization level is indicated in parentheses.
 //! - The numbers reported correspond to the successful path (i.e. `Some` is returned by `dequeue`
 //! and `Ok` is returned by `enqueue`).
+//! - **Stale**: these numbers predate the lap-based position scheme (full `usize` sequence
+//!   compares and lap/index arithmetic in place of the original `i8`-truncated diff trick) and the
+//!   per-iteration closed-bit check `enqueue` now does, both of which add work to the hot path.
+//!   They're kept as a rough historical baseline, not a current measurement; re-benchmark before
+//!   relying on them.
 //!
 //! # Portability
 //!
@@ -78,7 +94,14 @@
 //!
 //! # References
 //!
-//! This is an implementation of Dmitry Vyukov's ["Bounded MPMC queue"][0] minus the cache padding.
+//! This is an implementation of Dmitry Vyukov's ["Bounded MPMC queue"][0]. `enqueue_pos`,
+//! `dequeue_pos` and each cell's `sequence` can optionally be padded to a cache line to avoid
+//! false sharing between producers and consumers, in the same spirit as `crossbeam`'s
+//! `CachePadded`, gated behind the `mpmc_large_align` feature (see that type's docs).
+//!
+//! Positions are tracked with the "lap" scheme used by `crossbeam`'s `ArrayQueue`: a position is
+//! split into a lap and an index into a `(N + 1).next_power_of_two()`-sized window, so `N` need
+//! not be a power of two.
 //!
 //! [0]: http://www.1024cores.net/home/lock-free-algorithms/queues/bounded-mpmc-queue
 
@@ -108,15 +131,52 @@ pub type Q32<T> = MpMcQueue<T, 32>;
 /// MPMC queue with a capability for 64 elements.
 pub type Q64<T> = MpMcQueue<T, 64>;
 
+/// The error returned by [`MpMcQueue::enqueue`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum EnqueueError<T> {
+    /// The queue is full.
+    Full(T),
+    /// The queue has been [closed](MpMcQueue::close) and no longer accepts new items.
+    Closed(T),
+}
+
+impl<T> EnqueueError<T> {
+    /// Returns the item that could not be enqueued.
+    pub fn into_inner(self) -> T {
+        match self {
+            EnqueueError::Full(item) | EnqueueError::Closed(item) => item,
+        }
+    }
+}
+
+/// The queue has been [closed](MpMcQueue::close) and fully drained; no more items will arrive.
+///
+/// Returned by [`MpMcQueue::dequeue_or_closed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closed;
+
+// The top bit of `enqueue_pos`, used to signal that the queue has been closed. Folding the flag
+// into the same atomic that `enqueue`'s CAS loop already operates on makes closing and reserving a
+// slot mutually exclusive: whichever CAS commits first forces the other to retry and observe the
+// result, so a producer can never complete a reservation once `close` has taken effect, and
+// `close` can never erase a reservation that already succeeded. Position arithmetic always masks
+// this bit off first; real positions never come remotely close to it.
+const CLOSED_BIT: usize = 1 << (usize::BITS - 1);
+
 /// MPMC queue with a capacity for N elements
 pub struct MpMcQueue<T, const N: usize> {
     buffer: UnsafeCell<[Cell<T>; N]>,
-    dequeue_pos: AtomicUsize,
-    enqueue_pos: AtomicUsize,
+    dequeue_pos: CachePadded<AtomicUsize>,
+    enqueue_pos: CachePadded<AtomicUsize>,
 }
 
 impl<T, const N: usize> MpMcQueue<T, N> {
-    const MASK: usize = N - 1;
+    // The smallest power of two that is > `N`. Positions are split into a `lap` (the high bits)
+    // and an `index` (`pos & (ONE_LAP - 1)`, kept < `N` by the wraparound logic in `enqueue` /
+    // `dequeue`), which lets the queue support any `N >= 1` instead of requiring a power of two.
+    // Keeping it strictly above `N` means a filled cell's stamp (`pos + 1`) never equals the
+    // position the next lap's `enqueue` uses for that cell, even for `N == 1`.
+    const ONE_LAP: usize = (N + 1).next_power_of_two();
     const EMPTY_CELL: Cell<T> = Cell::new(0);
 
     /// Creates an empty queue
@@ -131,112 +191,573 @@ impl<T, const N: usize> MpMcQueue<T, N> {
 
         Self {
             buffer: UnsafeCell::new(result_cells),
-            dequeue_pos: AtomicUsize::new(0),
-            enqueue_pos: AtomicUsize::new(0),
+            dequeue_pos: CachePadded::new(AtomicUsize::new(0)),
+            enqueue_pos: CachePadded::new(AtomicUsize::new(0)),
         }
     }
 
+    /// Returns the queue's capacity (`N`).
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of elements currently in the queue.
+    ///
+    /// This is a best-effort snapshot: under concurrent enqueues/dequeues the true length may
+    /// already have changed by the time the caller observes the result.
+    pub fn len(&self) -> usize {
+        // `dequeue_pos` must be read *before* `enqueue_pos`. Both only ever increase, and
+        // `dequeue_pos <= enqueue_pos` always holds, so reading them in this order guarantees
+        // `dequeue_seq <= enqueue_seq` at the time `enqueue_seq` is taken: the first read can only
+        // be stale on the low side. Reading them the other way around lets a concurrent
+        // drain-and-refill push `dequeue_seq` past an already-stale `enqueue_seq`, underflowing
+        // `wrapping_sub`; `.min(N)` would then clamp that huge wrapped value back down to `N`,
+        // falsely reporting the queue as full.
+        let dequeue_seq =
+            to_logical_pos(self.dequeue_pos.load(Ordering::Acquire), Self::ONE_LAP, N);
+        let enqueue_pos = self.enqueue_pos.load(Ordering::Acquire) & !CLOSED_BIT;
+        let enqueue_seq = to_logical_pos(enqueue_pos, Self::ONE_LAP, N);
+        enqueue_seq.wrapping_sub(dequeue_seq).min(N)
+    }
+
+    /// Returns `true` if the queue holds no elements.
+    ///
+    /// Like [`len`](Self::len), this is a best-effort snapshot.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the queue is at capacity.
+    ///
+    /// Like [`len`](Self::len), this is a best-effort snapshot.
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
     /// Returns the item in the front of the queue, or `None` if the queue is empty
     pub fn dequeue(&self) -> Option<T> {
-        unsafe { dequeue(self.buffer.get() as *mut _, &self.dequeue_pos, Self::MASK) }
+        unsafe {
+            dequeue(
+                self.buffer.get() as *mut _,
+                &self.dequeue_pos,
+                Self::ONE_LAP,
+                N,
+            )
+        }
     }
 
     /// Adds an `item` to the end of the queue
     ///
-    /// Returns back the `item` if the queue is full
-    pub fn enqueue(&self, item: T) -> Result<(), T> {
+    /// Returns the `item` back, wrapped in an [`EnqueueError`], if the queue is full or has been
+    /// [closed](Self::close).
+    pub fn enqueue(&self, item: T) -> Result<(), EnqueueError<T>> {
         unsafe {
             enqueue(
                 self.buffer.get() as *mut _,
                 &self.enqueue_pos,
-                Self::MASK,
+                Self::ONE_LAP,
+                N,
                 item,
             )
         }
     }
+
+    /// Removes and returns the item at the front of the queue, or `None` if the queue is empty.
+    ///
+    /// Returns `Err(Closed)` instead of `Ok(None)` once the queue has been [closed](Self::close)
+    /// and fully drained, so a consumer can tell "temporarily empty" apart from "no more items
+    /// will ever arrive".
+    pub fn dequeue_or_closed(&self) -> Result<Option<T>, Closed> {
+        if let Some(item) = self.dequeue() {
+            return Ok(Some(item));
+        }
+
+        if !self.is_closed() {
+            return Ok(None);
+        }
+
+        // The queue looked closed-and-empty. Since `close` and a producer's slot reservation are
+        // mutually exclusive CAS attempts on `enqueue_pos` (see `CLOSED_BIT`), `enqueue_pos` no
+        // longer moves. A producer that reserved its slot before `close` may still be finishing
+        // its write, though, so the queue only counts as drained once `dequeue_pos` has caught up
+        // with `enqueue_pos`; until then this reports a temporary `Ok(None)`.
+        match self.dequeue() {
+            Some(item) => Ok(Some(item)),
+            None if self.is_empty() => Err(Closed),
+            None => Ok(None),
+        }
+    }
+
+    /// Closes the queue: every subsequent [`enqueue`](Self::enqueue) call fails with
+    /// [`EnqueueError::Closed`], letting consumers drain whatever is left and then stop.
+    pub fn close(&self) {
+        let mut pos = self.enqueue_pos.load(Ordering::Acquire);
+        while pos & CLOSED_BIT == 0 {
+            match self.enqueue_pos.compare_exchange_weak(
+                pos,
+                pos | CLOSED_BIT,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return,
+                Err(current) => pos = current,
+            }
+        }
+    }
+
+    /// Returns `true` if [`close`](Self::close) has been called.
+    pub fn is_closed(&self) -> bool {
+        self.enqueue_pos.load(Ordering::Acquire) & CLOSED_BIT != 0
+    }
+
+    /// Adds `item` to the end of the queue, waiting according to `W` until there is room.
+    ///
+    /// Returns the `item` back if the queue is [closed](Self::close) instead of waiting forever.
+    pub fn blocking_enqueue<W: WaitStrategy>(&self, item: T) -> Result<(), T> {
+        let strategy = W::default();
+        let mut item = item;
+        loop {
+            match self.enqueue(item) {
+                Ok(()) => {
+                    strategy.notify();
+                    return Ok(());
+                }
+                Err(EnqueueError::Closed(returned)) => return Err(returned),
+                Err(EnqueueError::Full(returned)) => {
+                    item = returned;
+                    strategy.wait();
+                }
+            }
+        }
+    }
+
+    /// Removes and returns the item at the front of the queue, waiting according to `W` until one
+    /// is available, or `None` once the queue is [closed](Self::close) and drained.
+    pub fn blocking_dequeue<W: WaitStrategy>(&self) -> Option<T> {
+        let strategy = W::default();
+        loop {
+            match self.dequeue_or_closed() {
+                Ok(Some(item)) => {
+                    strategy.notify();
+                    return Some(item);
+                }
+                Err(Closed) => return None,
+                Ok(None) => strategy.wait(),
+            }
+        }
+    }
+
+    /// Like [`blocking_enqueue`](Self::blocking_enqueue), but also gives up and returns `item`
+    /// back if `deadline` passes before there is room.
+    #[cfg(feature = "std")]
+    pub fn enqueue_timeout<W: WaitStrategy>(
+        &self,
+        item: T,
+        deadline: std::time::Instant,
+    ) -> Result<(), T> {
+        let strategy = W::default();
+        let mut item = item;
+        loop {
+            match self.enqueue(item) {
+                Ok(()) => {
+                    strategy.notify();
+                    return Ok(());
+                }
+                Err(EnqueueError::Closed(returned)) => return Err(returned),
+                Err(EnqueueError::Full(returned)) => {
+                    item = returned;
+                    if std::time::Instant::now() >= deadline {
+                        return Err(item);
+                    }
+                    strategy.wait();
+                }
+            }
+        }
+    }
+
+    /// Like [`blocking_dequeue`](Self::blocking_dequeue), but also gives up and returns `None` if
+    /// `deadline` passes before an item is available.
+    #[cfg(feature = "std")]
+    pub fn dequeue_timeout<W: WaitStrategy>(&self, deadline: std::time::Instant) -> Option<T> {
+        let strategy = W::default();
+        loop {
+            match self.dequeue_or_closed() {
+                Ok(Some(item)) => {
+                    strategy.notify();
+                    return Some(item);
+                }
+                Err(Closed) => return None,
+                Ok(None) => {
+                    if std::time::Instant::now() >= deadline {
+                        return None;
+                    }
+                    strategy.wait();
+                }
+            }
+        }
+    }
+
+    /// Adds as many of `items` as there is room for, in order, to the end of the queue.
+    ///
+    /// Returns the number of items actually enqueued, which is `items.len()` unless the queue
+    /// fills up first, or `0` if the queue has been [closed](Self::close). Reserves its slots
+    /// with a single compare-and-swap rather than one per item, which matters under contention.
+    pub fn enqueue_slice(&self, items: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        unsafe {
+            enqueue_slice(
+                self.buffer.get() as *mut _,
+                &self.enqueue_pos,
+                Self::ONE_LAP,
+                N,
+                items,
+            )
+        }
+    }
+
+    /// Removes as many items as there is room for in `out`, in order, from the front of the
+    /// queue, writing them into `out`.
+    ///
+    /// Returns the number of items actually dequeued, which is `out.len()` unless the queue
+    /// empties first. The corresponding prefix of `out` is initialized; the rest is left
+    /// untouched. Reserves its slots with a single compare-and-swap rather than one per item,
+    /// which matters under contention.
+    pub fn dequeue_slice(&self, out: &mut [MaybeUninit<T>]) -> usize {
+        unsafe {
+            dequeue_slice(
+                self.buffer.get() as *mut _,
+                &self.dequeue_pos,
+                Self::ONE_LAP,
+                N,
+                out,
+            )
+        }
+    }
+
+    /// Returns an iterator that dequeues items from the front of the queue until it is empty.
+    pub fn drain(&self) -> Drain<'_, T, N> {
+        Drain { queue: self }
+    }
+}
+
+/// An iterator that dequeues items from a [`MpMcQueue`] until it is empty.
+///
+/// Created by [`MpMcQueue::drain`].
+pub struct Drain<'a, T, const N: usize> {
+    queue: &'a MpMcQueue<T, N>,
+}
+
+impl<T, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.queue.dequeue()
+    }
 }
 
 unsafe impl<T, const N: usize> Sync for MpMcQueue<T, N> where T: Send {}
 
 struct Cell<T> {
     data: MaybeUninit<T>,
-    sequence: AtomicUsize,
+    sequence: CachePadded<AtomicUsize>,
 }
 
 impl<T> Cell<T> {
     const fn new(seq: usize) -> Self {
         Self {
             data: MaybeUninit::uninit(),
-            sequence: AtomicUsize::new(seq),
+            sequence: CachePadded::new(AtomicUsize::new(seq)),
+        }
+    }
+}
+
+/// Pads and aligns a value to the size of a cache line.
+///
+/// `dequeue_pos` and `enqueue_pos` (and each `Cell`'s `sequence`) are bumped by different
+/// threads. Without padding they end up sharing a cache line, so every producer CAS would
+/// invalidate the consumer's cached copy and vice versa ("false sharing"). Aligning each one to
+/// its own cache line lets producers and consumers touch disjoint lines, as Vyukov's algorithm
+/// intends.
+///
+/// This only pays for itself on multicore targets contending on the same queue: the padding
+/// inflates `size_of::<MpMcQueue<T, N>>()` by roughly `(N + 2) * line_size` bytes (64 or 128
+/// depending on target), which is significant for the small `N` this crate is built for on
+/// single-core,
+/// tiny-RAM embedded targets. So the padding is opt-in behind the `mpmc_large_align` feature;
+/// without it `CachePadded` is a zero-cost transparent wrapper and `MpMcQueue` keeps its natural,
+/// unpadded layout. Enable the feature if your queue is actually shared across cores/cache
+/// domains and the false-sharing cost outweighs the memory cost.
+///
+/// The alignment is widened to 128 bytes on architectures known to use (or prefetch) larger
+/// lines; everything else gets the common 64-byte line size.
+///
+/// `mpmc_large_align` must also be declared under `[features]` in this crate's `Cargo.toml`; it
+/// is not implicitly defined by using it in a `cfg`. Without that declaration `cargo`/`rustc`
+/// reject `--features mpmc_large_align` outright, and a default build without the flag emits an
+/// `unexpected_cfgs` warning (promoted to an error under `-D warnings`) pointing back here, so the
+/// gate can't silently evaluate to "always padded" or "always unpadded" without being noticed.
+#[cfg_attr(
+    all(
+        feature = "mpmc_large_align",
+        any(
+            target_arch = "x86_64",
+            target_arch = "aarch64",
+            target_arch = "powerpc64"
+        )
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(
+    all(
+        feature = "mpmc_large_align",
+        not(any(
+            target_arch = "x86_64",
+            target_arch = "aarch64",
+            target_arch = "powerpc64"
+        ))
+    ),
+    repr(align(64))
+)]
+struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    const fn new(value: T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> core::ops::Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> core::ops::DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// A pluggable strategy for [`MpMcQueue::blocking_enqueue`]/[`MpMcQueue::blocking_dequeue`] (and
+/// their `_timeout` counterparts), controlling how the calling thread waits between unsuccessful
+/// attempts to make progress.
+///
+/// Implementations that actually put the thread to sleep (e.g. [`Parking`]) rely on
+/// [`notify`](Self::notify) being called after every successful blocking operation to wake
+/// parked waiters back up; the blocking methods on [`MpMcQueue`] already do this for you.
+///
+/// On a microcontroller, implement this trait yourself and call `cortex_m::asm::wfi()` from
+/// [`wait`](Self::wait) to sleep the core until the next interrupt instead of spinning.
+pub trait WaitStrategy: Default {
+    /// Waits a bit before the caller retries the operation.
+    fn wait(&self);
+
+    /// Called after a successful `enqueue`/`dequeue`. The default implementation is a no-op,
+    /// which is correct for strategies that never put the calling thread to sleep.
+    fn notify(&self) {}
+}
+
+/// Spins in a tight loop using [`core::hint::spin_loop`].
+///
+/// Burns CPU continuously but has the lowest latency of the bundled strategies; best suited to
+/// waits that are expected to be very short.
+#[derive(Default)]
+pub struct Spin;
+
+impl WaitStrategy for Spin {
+    fn wait(&self) {
+        core::hint::spin_loop();
+    }
+}
+
+/// Spins with an exponentially increasing number of [`core::hint::spin_loop`] iterations, then
+/// (on `std`) falls back to yielding the thread to the scheduler.
+///
+/// Modeled on `crossbeam`'s `Backoff`: cheap for the first few retries, then gets out of other
+/// threads' way instead of burning CPU indefinitely.
+pub struct Backoff {
+    step: core::cell::Cell<u32>,
+}
+
+impl Backoff {
+    const SPIN_LIMIT: u32 = 6;
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            step: core::cell::Cell::new(0),
+        }
+    }
+}
+
+impl WaitStrategy for Backoff {
+    fn wait(&self) {
+        let step = self.step.get();
+
+        if step <= Self::SPIN_LIMIT {
+            for _ in 0..1u32 << step {
+                core::hint::spin_loop();
+            }
+            self.step.set(step + 1);
+        } else {
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+
+            #[cfg(not(feature = "std"))]
+            for _ in 0..1u32 << Self::SPIN_LIMIT {
+                core::hint::spin_loop();
+            }
         }
     }
 }
 
-unsafe fn dequeue<T>(buffer: *mut Cell<T>, dequeue_pos: &AtomicUsize, mask: usize) -> Option<T> {
-    let mut pos = dequeue_pos.load(Ordering::Relaxed);
+/// Parks the calling thread and wakes it up again shortly after any queue using this strategy
+/// makes progress, instead of spinning.
+///
+/// Waiters are woken through a process-wide condition variable shared by every [`Parking`]
+/// instance, so a [`notify`](WaitStrategy::notify) may also wake threads blocked on an unrelated
+/// queue; they simply find no work and go back to waiting. Bounding each park with a short
+/// timeout guards against ever missing a wakeup.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct Parking;
+
+#[cfg(feature = "std")]
+impl Parking {
+    const PARK_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(1);
+
+    fn gate() -> &'static (std::sync::Mutex<()>, std::sync::Condvar) {
+        static GATE: std::sync::OnceLock<(std::sync::Mutex<()>, std::sync::Condvar)> =
+            std::sync::OnceLock::new();
+        GATE.get_or_init(|| (std::sync::Mutex::new(()), std::sync::Condvar::new()))
+    }
+}
+
+#[cfg(feature = "std")]
+impl WaitStrategy for Parking {
+    fn wait(&self) {
+        let (lock, condvar) = Self::gate();
+        if let Ok(guard) = lock.lock() {
+            let _ = condvar.wait_timeout(guard, Self::PARK_TIMEOUT);
+        }
+    }
+
+    fn notify(&self) {
+        Self::gate().1.notify_all();
+    }
+}
+
+// Converts a raw `enqueue_pos`/`dequeue_pos` into the number of slots it represents having
+// advanced through. Raw positions aren't contiguous counts when `cap` isn't a power of two (each
+// lap skips `one_lap - cap` unused position values), so this scales the lap number back down to
+// `cap` slots per lap before adding the index back in.
+fn to_logical_pos(pos: usize, one_lap: usize, cap: usize) -> usize {
+    let index = pos & (one_lap - 1);
+    let lap_number = pos / one_lap;
+    lap_number.wrapping_mul(cap).wrapping_add(index)
+}
+
+// Computes the position one slot past `pos`, wrapping to the next lap once `cap` slots have been
+// consumed out of the current `one_lap`-sized window.
+fn advance_pos(pos: usize, one_lap: usize, cap: usize) -> usize {
+    let index = pos & (one_lap - 1);
+    let lap = pos & !(one_lap - 1);
+
+    if index + 1 < cap {
+        pos.wrapping_add(1)
+    } else {
+        lap.wrapping_add(one_lap)
+    }
+}
+
+// `one_lap` and `cap` (== `N`) are passed in rather than read off a `MpMcQueue<T, N>` so that
+// `dequeue`/`enqueue` stay generic over `T` alone, not over `N` as well (avoids monomorphizing a
+// copy of the CAS loop per capacity).
+unsafe fn dequeue<T>(
+    buffer: *mut Cell<T>,
+    dequeue_pos: &AtomicUsize,
+    one_lap: usize,
+    cap: usize,
+) -> Option<T> {
+    let mut pos = dequeue_pos.load(Ordering::Acquire);
 
     let mut cell;
     loop {
-        cell = buffer.add(usize::from(pos & mask));
+        let index = pos & (one_lap - 1);
+        cell = buffer.add(index);
         let seq = (*cell).sequence.load(Ordering::Acquire);
-        let dif = (seq as i8).wrapping_sub((pos.wrapping_add(1)) as i8);
-
-        if dif == 0 {
-            if dequeue_pos
-                .compare_exchange_weak(
-                    pos,
-                    pos.wrapping_add(1),
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                )
-                .is_ok()
-            {
-                break;
+
+        if seq == pos.wrapping_add(1) {
+            let new_pos = advance_pos(pos, one_lap, cap);
+
+            match dequeue_pos.compare_exchange_weak(
+                pos,
+                new_pos,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(current) => pos = current,
             }
-        } else if dif < 0 {
+        } else if seq == pos {
             return None;
         } else {
-            pos = dequeue_pos.load(Ordering::Relaxed);
+            pos = dequeue_pos.load(Ordering::Acquire);
         }
     }
 
     let data = (*cell).data.as_ptr().read();
     (*cell)
         .sequence
-        .store(pos.wrapping_add(mask).wrapping_add(1), Ordering::Release);
+        .store(pos.wrapping_add(one_lap), Ordering::Release);
     Some(data)
 }
 
 unsafe fn enqueue<T>(
     buffer: *mut Cell<T>,
     enqueue_pos: &AtomicUsize,
-    mask: usize,
+    one_lap: usize,
+    cap: usize,
     item: T,
-) -> Result<(), T> {
-    let mut pos = enqueue_pos.load(Ordering::Relaxed);
+) -> Result<(), EnqueueError<T>> {
+    let mut pos = enqueue_pos.load(Ordering::Acquire);
 
     let mut cell;
     loop {
-        cell = buffer.add(usize::from(pos & mask));
+        // Checked on every iteration (not just once up front) so that a `close` which wins its
+        // CAS against our reservation attempt below is caught as soon as we reload `pos` and
+        // retry, rather than racing ahead on a stale, pre-close position.
+        if pos & CLOSED_BIT != 0 {
+            return Err(EnqueueError::Closed(item));
+        }
+
+        let index = pos & (one_lap - 1);
+        cell = buffer.add(index);
         let seq = (*cell).sequence.load(Ordering::Acquire);
-        let dif = (seq as i8).wrapping_sub(pos as i8);
-
-        if dif == 0 {
-            if enqueue_pos
-                .compare_exchange_weak(
-                    pos,
-                    pos.wrapping_add(1),
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                )
-                .is_ok()
-            {
-                break;
+
+        if seq == pos {
+            let new_pos = advance_pos(pos, one_lap, cap);
+
+            match enqueue_pos.compare_exchange_weak(
+                pos,
+                new_pos,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(current) => pos = current,
             }
-        } else if dif < 0 {
-            return Err(item);
+        } else if seq.wrapping_add(one_lap) == pos.wrapping_add(1) {
+            return Err(EnqueueError::Full(item));
         } else {
-            pos = enqueue_pos.load(Ordering::Relaxed);
+            pos = enqueue_pos.load(Ordering::Acquire);
         }
     }
 
@@ -247,9 +768,121 @@ unsafe fn enqueue<T>(
     Ok(())
 }
 
+// Reserves a contiguous run of up to `items.len()` positions with a single CAS on
+// `enqueue_pos`, then publishes each one. The probe only ever shrinks the batch, so it never
+// reserves past a slot that a consumer hasn't vacated yet.
+unsafe fn enqueue_slice<T>(
+    buffer: *mut Cell<T>,
+    enqueue_pos: &AtomicUsize,
+    one_lap: usize,
+    cap: usize,
+    items: &[T],
+) -> usize
+where
+    T: Copy,
+{
+    if items.is_empty() {
+        return 0;
+    }
+
+    loop {
+        let start = enqueue_pos.load(Ordering::Acquire);
+        if start & CLOSED_BIT != 0 {
+            return 0;
+        }
+
+        let mut pos = start;
+        let mut k = 0;
+        while k < items.len() {
+            let index = pos & (one_lap - 1);
+            let seq = (*buffer.add(index)).sequence.load(Ordering::Acquire);
+            if seq != pos {
+                break;
+            }
+            pos = advance_pos(pos, one_lap, cap);
+            k += 1;
+        }
+
+        if k == 0 {
+            return 0;
+        }
+
+        if enqueue_pos
+            .compare_exchange_weak(start, pos, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            let mut pos = start;
+            for &item in &items[..k] {
+                let cell = buffer.add(pos & (one_lap - 1));
+                (*cell).data.as_mut_ptr().write(item);
+                (*cell)
+                    .sequence
+                    .store(pos.wrapping_add(1), Ordering::Release);
+                pos = advance_pos(pos, one_lap, cap);
+            }
+            return k;
+        }
+    }
+}
+
+// Reserves a contiguous run of up to `out.len()` positions with a single CAS on `dequeue_pos`,
+// then reads each one out. Symmetric to `enqueue_slice`.
+unsafe fn dequeue_slice<T>(
+    buffer: *mut Cell<T>,
+    dequeue_pos: &AtomicUsize,
+    one_lap: usize,
+    cap: usize,
+    out: &mut [MaybeUninit<T>],
+) -> usize {
+    if out.is_empty() {
+        return 0;
+    }
+
+    loop {
+        let start = dequeue_pos.load(Ordering::Acquire);
+
+        let mut pos = start;
+        let mut k = 0;
+        while k < out.len() {
+            let index = pos & (one_lap - 1);
+            let seq = (*buffer.add(index)).sequence.load(Ordering::Acquire);
+            if seq != pos.wrapping_add(1) {
+                break;
+            }
+            pos = advance_pos(pos, one_lap, cap);
+            k += 1;
+        }
+
+        if k == 0 {
+            return 0;
+        }
+
+        if dequeue_pos
+            .compare_exchange_weak(start, pos, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            let mut pos = start;
+            for slot in &mut out[..k] {
+                let cell = buffer.add(pos & (one_lap - 1));
+                slot.write((*cell).data.as_ptr().read());
+                (*cell)
+                    .sequence
+                    .store(pos.wrapping_add(one_lap), Ordering::Release);
+                pos = advance_pos(pos, one_lap, cap);
+            }
+            return k;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Q2;
+    use core::mem::MaybeUninit;
+
+    use super::{Backoff, Closed, EnqueueError, MpMcQueue, Spin, WaitStrategy, Q2};
+
+    #[cfg(feature = "std")]
+    use super::Parking;
 
     #[test]
     fn sanity() {
@@ -286,4 +919,152 @@ mod tests {
         // this should not block forever
         assert!(q.enqueue(0).is_err());
     }
+
+    #[test]
+    fn non_power_of_two_capacity() {
+        let q: MpMcQueue<u8, 3> = MpMcQueue::new();
+        q.enqueue(0).unwrap();
+        q.enqueue(1).unwrap();
+        q.enqueue(2).unwrap();
+        assert!(q.enqueue(3).is_err());
+
+        assert_eq!(q.dequeue(), Some(0));
+        // wraps around the lap boundary without losing slot 2 or corrupting the mask
+        q.enqueue(3).unwrap();
+        assert_eq!(q.dequeue(), Some(1));
+        assert_eq!(q.dequeue(), Some(2));
+        assert_eq!(q.dequeue(), Some(3));
+        assert_eq!(q.dequeue(), None);
+    }
+
+    #[test]
+    fn blocking_enqueue_dequeue() {
+        let q: MpMcQueue<u8, 2> = MpMcQueue::new();
+        q.blocking_enqueue::<Spin>(1).unwrap();
+        q.blocking_enqueue::<Spin>(2).unwrap();
+        assert_eq!(q.blocking_dequeue::<Spin>(), Some(1));
+        assert_eq!(q.blocking_dequeue::<Spin>(), Some(2));
+    }
+
+    #[test]
+    fn blocking_enqueue_dequeue_with_backoff() {
+        let q: MpMcQueue<u8, 2> = MpMcQueue::new();
+        q.blocking_enqueue::<Backoff>(1).unwrap();
+        q.blocking_enqueue::<Backoff>(2).unwrap();
+        assert_eq!(q.blocking_dequeue::<Backoff>(), Some(1));
+        assert_eq!(q.blocking_dequeue::<Backoff>(), Some(2));
+    }
+
+    #[test]
+    fn backoff_wait_advances_past_the_spin_limit_without_panicking() {
+        let backoff = Backoff::default();
+        // A few calls past SPIN_LIMIT exercise both the spinning phase and the
+        // yield-the-thread (or, without `std`, bounded-spin) fallback it falls back to.
+        for _ in 0..(Backoff::SPIN_LIMIT as usize + 3) {
+            backoff.wait();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn enqueue_timeout_gives_up_once_deadline_passes() {
+        let q: MpMcQueue<u8, 2> = MpMcQueue::new();
+        q.enqueue(1).unwrap();
+        q.enqueue(2).unwrap();
+
+        // The queue is full and nothing ever drains it in this test, so this deterministically
+        // exercises the deadline path rather than racing against a concurrent dequeue.
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(5);
+        assert_eq!(q.enqueue_timeout::<Spin>(3, deadline), Err(3));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn dequeue_timeout_gives_up_once_deadline_passes() {
+        let q: MpMcQueue<u8, 2> = MpMcQueue::new();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(5);
+        assert_eq!(q.dequeue_timeout::<Spin>(deadline), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn parking_wakes_a_blocked_dequeue_once_another_thread_enqueues() {
+        let q: std::sync::Arc<MpMcQueue<u8, 1>> = std::sync::Arc::new(MpMcQueue::new());
+        let producer_q = std::sync::Arc::clone(&q);
+
+        let producer = std::thread::spawn(move || {
+            // Give the main thread a head start so it's actually parked (not just polling) when
+            // the enqueue below calls `notify`, exercising the condvar wake path rather than the
+            // bounded park timeout alone.
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            producer_q.blocking_enqueue::<Parking>(42).unwrap();
+        });
+
+        assert_eq!(q.blocking_dequeue::<Parking>(), Some(42));
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn close_stops_new_enqueues_and_drains_the_rest() {
+        let q: MpMcQueue<u8, 2> = MpMcQueue::new();
+        q.enqueue(1).unwrap();
+        q.close();
+
+        assert!(q.is_closed());
+        assert_eq!(q.enqueue(2), Err(EnqueueError::Closed(2)));
+        assert_eq!(q.blocking_enqueue::<Spin>(2), Err(2));
+
+        // consumers can still drain what was already enqueued...
+        assert_eq!(q.dequeue_or_closed(), Ok(Some(1)));
+        // ...and then observe that the queue is done for good.
+        assert_eq!(q.dequeue_or_closed(), Err(Closed));
+        assert_eq!(q.blocking_dequeue::<Spin>(), None);
+    }
+
+    #[test]
+    fn len_capacity_is_empty_is_full() {
+        let q: MpMcQueue<u8, 3> = MpMcQueue::new();
+        assert_eq!(q.capacity(), 3);
+        assert_eq!(q.len(), 0);
+        assert!(q.is_empty());
+        assert!(!q.is_full());
+
+        q.enqueue(1).unwrap();
+        q.enqueue(2).unwrap();
+        assert_eq!(q.len(), 2);
+        assert!(!q.is_empty());
+        assert!(!q.is_full());
+
+        q.enqueue(3).unwrap();
+        assert_eq!(q.len(), 3);
+        assert!(q.is_full());
+
+        // len() keeps reporting correctly across a lap wraparound.
+        assert_eq!(q.dequeue(), Some(1));
+        q.enqueue(4).unwrap();
+        assert_eq!(q.len(), 3);
+        assert!(q.is_full());
+    }
+
+    #[test]
+    fn enqueue_dequeue_slice() {
+        let q: MpMcQueue<u8, 3> = MpMcQueue::new();
+
+        assert_eq!(q.enqueue_slice(&[1, 2, 3, 4]), 3);
+        assert_eq!(q.enqueue_slice(&[5]), 0);
+
+        let mut out = [MaybeUninit::uninit(); 2];
+        assert_eq!(q.dequeue_slice(&mut out), 2);
+        assert_eq!(unsafe { out[0].assume_init() }, 1);
+        assert_eq!(unsafe { out[1].assume_init() }, 2);
+
+        assert_eq!(q.enqueue_slice(&[5, 6]), 2);
+        let mut drained = [0u8; 3];
+        for (slot, item) in drained.iter_mut().zip(q.drain()) {
+            *slot = item;
+        }
+        assert_eq!(drained, [3, 5, 6]);
+        assert_eq!(q.dequeue(), None);
+    }
 }